@@ -0,0 +1,185 @@
+//! Types representing a parsed patch
+
+mod parse;
+
+pub use parse::{
+    parse_lenient, FileMetadata, LineEnding, ParsePatchError, ParsePatchErrorKind, PatchSet,
+};
+
+use crate::utils::Text;
+use std::borrow::Cow;
+
+pub(crate) const NO_NEWLINE_AT_EOF: &str = "\\ No newline at end of file";
+pub(crate) const ESCAPED_CHARS_BYTES: &[u8] = &[b'\n', b'\t', b'\0', b'\r', b'"', b'\\'];
+
+/// A parsed patch describing the changes to a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch<'a, T: ToOwned + ?Sized> {
+    original: Cow<'a, T>,
+    modified: Cow<'a, T>,
+    metadata: FileMetadata<'a, T>,
+    line_ending: LineEnding,
+    hunks: Vec<Hunk<'a, T>>,
+}
+
+impl<'a, T: ToOwned + ?Sized> Patch<'a, T> {
+    pub(crate) fn new(
+        original: Cow<'a, T>,
+        modified: Cow<'a, T>,
+        metadata: FileMetadata<'a, T>,
+        line_ending: LineEnding,
+        hunks: Vec<Hunk<'a, T>>,
+    ) -> Self {
+        Self {
+            original,
+            modified,
+            metadata,
+            line_ending,
+            hunks,
+        }
+    }
+
+    /// The name of the original file, from the `--- ` header; for a git change
+    /// with no hunk body, from its `rename from`/`copy from` line, or empty if
+    /// it has neither
+    pub fn original(&self) -> &T {
+        &self.original
+    }
+
+    /// The name of the modified file, from the `+++ ` header; for a git change
+    /// with no hunk body, from its `rename to`/`copy to` line, or empty if it
+    /// has neither
+    pub fn modified(&self) -> &T {
+        &self.modified
+    }
+
+    /// The git extended-header metadata (renames, mode changes, etc.) for this
+    /// file, or the default empty value for a plain unified diff
+    pub fn metadata(&self) -> &FileMetadata<'a, T> {
+        &self.metadata
+    }
+
+    /// The line ending (LF or CRLF) detected in the patch, so the writer can
+    /// reproduce the input's convention
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// The hunks making up the patch
+    pub fn hunks(&self) -> &[Hunk<'a, T>] {
+        &self.hunks
+    }
+}
+
+impl<'a> Patch<'a, str> {
+    /// Parse a `Patch` from a string
+    pub fn from_str(s: &'a str) -> Result<Patch<'a, str>, ParsePatchError> {
+        parse::parse(s)
+    }
+}
+
+impl<'a> Patch<'a, [u8]> {
+    /// Parse a `Patch` from bytes
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Patch<'a, [u8]>, ParsePatchError> {
+        parse::parse_bytes(bytes)
+    }
+}
+
+/// The range of lines a `Hunk` applies to, as `start,len`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HunkRange {
+    pub(crate) start: usize,
+    pub(crate) len: usize,
+}
+
+impl HunkRange {
+    pub(crate) fn new(start: usize, len: usize) -> Self {
+        Self { start, len }
+    }
+
+    /// The starting line number (1-based) of the range
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The number of lines in the range
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the range is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The line number one past the end of the range
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+}
+
+/// A single line within a `Hunk`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Line<'a, T: ?Sized> {
+    /// A line unchanged between the two files
+    Context(&'a T),
+    /// A line present only in the original file
+    Delete(&'a T),
+    /// A line present only in the modified file
+    Insert(&'a T),
+}
+
+/// A contiguous group of changed lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk<'a, T: ?Sized> {
+    pub(crate) old_range: HunkRange,
+    pub(crate) new_range: HunkRange,
+    function_context: Option<&'a T>,
+    lines: Vec<Line<'a, T>>,
+}
+
+impl<'a, T: ?Sized> Hunk<'a, T> {
+    pub(crate) fn new(
+        old_range: HunkRange,
+        new_range: HunkRange,
+        function_context: Option<&'a T>,
+        lines: Vec<Line<'a, T>>,
+    ) -> Self {
+        Self {
+            old_range,
+            new_range,
+            function_context,
+            lines,
+        }
+    }
+
+    /// The range of lines in the original file
+    pub fn old_range(&self) -> HunkRange {
+        self.old_range
+    }
+
+    /// The range of lines in the modified file
+    pub fn new_range(&self) -> HunkRange {
+        self.new_range
+    }
+
+    /// The function context trailing the `@@ ... @@` header, if any
+    pub fn function_context(&self) -> Option<&'a T> {
+        self.function_context
+    }
+
+    /// The lines making up the hunk
+    pub fn lines(&self) -> &[Line<'a, T>] {
+        &self.lines
+    }
+}
+
+// Count the number of lines a hunk touches in the original and modified files,
+// used to check a hunk body against its header's declared ranges.
+pub(crate) fn hunk_lines_count<T: ?Sized>(lines: &[Line<'_, T>]) -> (usize, usize) {
+    lines.iter().fold((0, 0), |(old, new), line| match line {
+        Line::Context(_) => (old + 1, new + 1),
+        Line::Delete(_) => (old + 1, new),
+        Line::Insert(_) => (old, new + 1),
+    })
+}
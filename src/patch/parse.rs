@@ -9,35 +9,143 @@ use std::{borrow::Cow, fmt};
 
 type Result<T, E = ParsePatchError> = std::result::Result<T, E>;
 
+/// The kind of failure encountered while parsing a `Patch`
+///
+/// Returned by [`ParsePatchError::kind`] so tools can programmatically react to
+/// the category of error rather than pattern-matching on the `Display` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParsePatchErrorKind {
+    /// The input ended before a complete patch could be parsed
+    UnexpectedEof,
+    /// A `--- `/`+++ ` file header line could not be parsed
+    BadFilename,
+    /// An `@@ ... @@` hunk header line could not be parsed
+    BadHunkHeader,
+    /// The line counts declared in a hunk header did not match the hunk body
+    HunkCountMismatch,
+    /// Two hunks were out of order or overlapped one another
+    HunksOutOfOrder,
+    /// A line inside a hunk body did not start with ` `, `-`, `+`, or `\`, or
+    /// was not allowed where it appeared (such as a line after a
+    /// `\ No newline at end of file` marker that closed that side of the hunk)
+    UnexpectedLine,
+    /// [`parse`] was given a patch containing more than one file section
+    MultipleFiles,
+}
+
+impl ParsePatchErrorKind {
+    fn description(self) -> &'static str {
+        match self {
+            ParsePatchErrorKind::UnexpectedEof => "unexpected end of input",
+            ParsePatchErrorKind::BadFilename => "malformed filename in file header",
+            ParsePatchErrorKind::BadHunkHeader => "malformed hunk header",
+            ParsePatchErrorKind::HunkCountMismatch => "hunk header does not match hunk body",
+            ParsePatchErrorKind::HunksOutOfOrder => "hunks are not in order or overlap",
+            ParsePatchErrorKind::UnexpectedLine => "unexpected line in hunk body",
+            ParsePatchErrorKind::MultipleFiles => {
+                "patch contains more than one file; use PatchSet"
+            }
+        }
+    }
+}
+
 /// An error returned when parsing a `Patch` using [`Patch::from_str`] fails
 ///
+/// The error carries the [`ParsePatchErrorKind`], the 1-based line number where
+/// parsing failed, and the raw text of the offending line so that tools can map
+/// a failure back to a position in the input.
+///
 /// [`Patch::from_str`]: struct.Patch.html#method.from_str
-// TODO use a custom error type instead of a Cow
 #[derive(Debug)]
-pub struct ParsePatchError(Cow<'static, str>);
+pub struct ParsePatchError {
+    kind: ParsePatchErrorKind,
+    line: usize,
+    text: String,
+}
 
 impl ParsePatchError {
-    fn new<E: Into<Cow<'static, str>>>(e: E) -> Self {
-        Self(e.into())
+    fn new<T: Text + ?Sized>(kind: ParsePatchErrorKind, line: usize, text: &T) -> Self {
+        Self {
+            kind,
+            line,
+            text: String::from_utf8_lossy(text.as_bytes()).into_owned(),
+        }
+    }
+
+    fn eof(line: usize) -> Self {
+        Self {
+            kind: ParsePatchErrorKind::UnexpectedEof,
+            line,
+            text: String::new(),
+        }
+    }
+
+    /// The kind of parse failure that occurred
+    pub fn kind(&self) -> ParsePatchErrorKind {
+        self.kind
+    }
+
+    /// The 1-based line number in the input at which parsing failed
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The raw text of the line that could not be parsed
+    ///
+    /// Empty for [`ParsePatchErrorKind::UnexpectedEof`] and for
+    /// [`ParsePatchErrorKind::HunksOutOfOrder`], which records only the line
+    /// number of the second hunk's header.
+    pub fn text(&self) -> &str {
+        &self.text
     }
 }
 
 impl fmt::Display for ParsePatchError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "error parsing patch: {}", self.0)
+        write!(
+            f,
+            "error parsing patch at line {}: {}",
+            self.line,
+            self.kind.description()
+        )
     }
 }
 
 impl std::error::Error for ParsePatchError {}
 
+/// The line terminator used by a patch, tracked so the writer can reproduce the
+/// input's convention rather than normalizing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix-style `\n`
+    Lf,
+    /// Windows-style `\r\n`
+    Crlf,
+}
+
+// Detect the line ending of a single line, defaulting to `Lf` for an
+// unterminated final line.
+fn detect_line_ending<T: Text + ?Sized>(line: &T) -> LineEnding {
+    if line.strip_suffix("\r\n").is_some() {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    }
+}
+
 struct Parser<'a, T: Text + ?Sized> {
     lines: std::iter::Peekable<LineIter<'a, T>>,
+    // 1-based number of the line most recently returned by `next`; a peeked but
+    // not-yet-consumed line is therefore at `line + 1`.
+    line: usize,
 }
 
 impl<'a, T: Text + ?Sized> Parser<'a, T> {
     fn new(input: &'a T) -> Self {
         Self {
             lines: LineIter::new(input).peekable(),
+            line: 0,
         }
     }
 
@@ -45,33 +153,183 @@ impl<'a, T: Text + ?Sized> Parser<'a, T> {
         self.lines.peek()
     }
 
+    // The 1-based line number of the line most recently consumed by `next`.
+    fn line_number(&self) -> usize {
+        self.line
+    }
+
+    // The 1-based line number of the line that `peek` would return next.
+    fn peek_line_number(&self) -> usize {
+        self.line + 1
+    }
+
     fn next(&mut self) -> Result<&'a T> {
-        let line = self
-            .lines
-            .next()
-            .ok_or_else(|| ParsePatchError::new("unexpected EOF"))?;
+        let line = self.lines.next().ok_or_else(|| Self::eof(self.line + 1))?;
+        self.line += 1;
         Ok(line)
     }
+
+    fn eof(line: usize) -> ParsePatchError {
+        ParsePatchError::eof(line)
+    }
 }
 
 pub fn parse<'a>(input: &'a str) -> Result<Patch<'a, str>> {
     let mut parser = Parser::new(input);
-    let header = patch_header(&mut parser)?;
-    let hunks = hunks(&mut parser)?;
+    let patch = parse_single(&mut parser, convert_cow_to_str)?;
+
+    // `parse` is the single-file convenience; reject input that holds more.
+    if parser.peek().is_some() {
+        let line = *parser.peek().unwrap();
+        return Err(ParsePatchError::new(
+            ParsePatchErrorKind::MultipleFiles,
+            parser.peek_line_number(),
+            line,
+        ));
+    }
 
-    Ok(Patch::new(
+    Ok(patch)
+}
+
+pub fn parse_bytes<'a>(input: &'a [u8]) -> Result<Patch<'a, [u8]>> {
+    let mut parser = Parser::new(input);
+    let patch = parse_single(&mut parser, |filename| filename)?;
+
+    if parser.peek().is_some() {
+        let line = *parser.peek().unwrap();
+        return Err(ParsePatchError::new(
+            ParsePatchErrorKind::MultipleFiles,
+            parser.peek_line_number(),
+            line,
+        ));
+    }
+
+    Ok(patch)
+}
+
+/// Parse a patch, collecting every diagnostic instead of stopping at the first.
+///
+/// Returns a best-effort [`Patch`] (or `None` when even the file header could
+/// not be parsed) together with all of the [`ParsePatchError`]s encountered.
+/// A malformed hunk is skipped by resynchronizing to the next `@@`/file
+/// boundary, and the hunk-ordering and line-count checks record their problems
+/// rather than aborting, so a single pass surfaces every issue.
+pub fn parse_lenient<'a>(input: &'a str) -> (Option<Patch<'a, str>>, Vec<ParsePatchError>) {
+    let mut parser = Parser::new(input);
+    let mut errors = Vec::new();
+
+    let line_ending = parser.peek().map(|line| detect_line_ending(*line)).unwrap_or(LineEnding::Lf);
+
+    let header = match patch_header(&mut parser) {
+        Ok(header) => header,
+        Err(e) => {
+            errors.push(e);
+            return (None, errors);
+        }
+    };
+
+    let hunks = hunks_lenient(&mut parser, &mut errors);
+    let patch = Patch::new(
         convert_cow_to_str(header.0),
         convert_cow_to_str(header.1),
+        header.2,
+        line_ending,
         hunks,
-    ))
+    );
+
+    (Some(patch), errors)
 }
 
-pub fn parse_bytes<'a>(input: &'a [u8]) -> Result<Patch<'a, [u8]>> {
+pub fn parse_patch_set<'a>(input: &'a str) -> Result<PatchSet<'a, str>> {
     let mut parser = Parser::new(input);
-    let header = patch_header(&mut parser)?;
-    let hunks = hunks(&mut parser)?;
+    let mut patches = Vec::new();
+    while parser.peek().is_some() {
+        patches.push(parse_single(&mut parser, convert_cow_to_str)?);
+    }
+
+    Ok(PatchSet { patches })
+}
+
+pub fn parse_patch_set_bytes<'a>(input: &'a [u8]) -> Result<PatchSet<'a, [u8]>> {
+    let mut parser = Parser::new(input);
+    let mut patches = Vec::new();
+    while parser.peek().is_some() {
+        patches.push(parse_single(&mut parser, |filename| filename)?);
+    }
+
+    Ok(PatchSet { patches })
+}
+
+// Parse the next file section (header + hunks up to the next file boundary)
+// out of `parser`. `convert` maps the raw `[u8]` filename cows onto the target
+// `Text` type so the same loop body serves both the `str` and `[u8]` parsers.
+fn parse_single<'a, T, F>(parser: &mut Parser<'a, T>, convert: F) -> Result<Patch<'a, T>>
+where
+    T: Text + ToOwned + ?Sized,
+    F: Fn(Cow<'a, [u8]>) -> Cow<'a, T>,
+{
+    // Detect the line ending from this section's first line so that a patch
+    // mixing LF and CRLF sections records the right ending on each file.
+    let line_ending = parser
+        .peek()
+        .map(|line| detect_line_ending(*line))
+        .unwrap_or(LineEnding::Lf);
+
+    let header = patch_header(parser)?;
+    let hunks = hunks(parser)?;
+    Ok(Patch::new(
+        convert(header.0),
+        convert(header.1),
+        header.2,
+        line_ending,
+        hunks,
+    ))
+}
+
+/// A sequence of [`Patch`]es parsed from a patch file that describes changes to
+/// more than one file, as produced by git, bzr, hg, and svn.
+///
+/// Use [`PatchSet::from_str`]/[`PatchSet::from_bytes`] to parse concatenated
+/// file diffs; the single-file [`parse`] errors if handed such input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchSet<'a, T: ?Sized> {
+    patches: Vec<Patch<'a, T>>,
+}
+
+impl<'a> PatchSet<'a, str> {
+    /// Parse a multi-file patch out of a string
+    pub fn from_str(s: &'a str) -> Result<PatchSet<'a, str>> {
+        parse_patch_set(s)
+    }
+}
 
-    Ok(Patch::new(header.0, header.1, hunks))
+impl<'a> PatchSet<'a, [u8]> {
+    /// Parse a multi-file patch out of bytes
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<PatchSet<'a, [u8]>> {
+        parse_patch_set_bytes(bytes)
+    }
+}
+
+impl<'a, T: ?Sized> PatchSet<'a, T> {
+    /// The per-file patches in the order they appeared in the input
+    pub fn patches(&self) -> &[Patch<'a, T>] {
+        &self.patches
+    }
+
+    /// Consume the set, returning the owned vector of per-file patches
+    pub fn into_patches(self) -> Vec<Patch<'a, T>> {
+        self.patches
+    }
+
+    /// The number of file sections in the set
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Whether the set contains no file sections
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
 }
 
 // This is only used when the type originated as a utf8 string
@@ -85,46 +343,220 @@ fn convert_cow_to_str(cow: Cow<'_, [u8]>) -> Cow<'_, str> {
 #[allow(clippy::type_complexity)]
 fn patch_header<'a, T: Text + ToOwned + ?Sized>(
     parser: &mut Parser<'a, T>,
-) -> Result<(Cow<'a, [u8]>, Cow<'a, [u8]>)> {
-    skip_header_preamble(parser)?;
-    let filename1 = parse_filename("--- ", parser.next()?)?;
-    let filename2 = parse_filename("+++ ", parser.next()?)?;
-    Ok((filename1, filename2))
+) -> Result<(Cow<'a, [u8]>, Cow<'a, [u8]>, FileMetadata<'a, T>)> {
+    let metadata = parse_git_preamble(parser)?;
+
+    let has_body = matches!(parser.peek(), Some(line) if line.starts_with("--- "));
+    let (filename1, filename2) = if has_body {
+        let line1 = parser.next()?;
+        let filename1 = parse_filename("--- ", parser.line_number(), line1)?;
+        let line2 = parser.next()?;
+        let filename2 = parse_filename("+++ ", parser.line_number(), line2)?;
+        (filename1, filename2)
+    } else {
+        // A rename-, copy-, or mode-only change has no "--- "/"+++ " pair and no
+        // hunk body; recover the paths from the git metadata instead.
+        let filename1 = metadata
+            .rename_from
+            .or(metadata.copy_from)
+            .map(|p| Cow::from(p.as_bytes()))
+            .unwrap_or_default();
+        let filename2 = metadata
+            .rename_to
+            .or(metadata.copy_to)
+            .map(|p| Cow::from(p.as_bytes()))
+            .unwrap_or_default();
+        (filename1, filename2)
+    };
+
+    Ok((filename1, filename2, metadata))
+}
+
+/// Git-specific metadata carried in the extended header lines that precede a
+/// file's `--- `/`+++ ` pair in a `diff --git` patch.
+///
+/// Each field holds the verbatim (newline-stripped) text that followed the
+/// corresponding header prefix, or `None` when that line was absent. A
+/// rename-only change may populate [`rename_from`]/[`rename_to`] without any
+/// hunk bodies at all.
+///
+/// [`rename_from`]: #structfield.rename_from
+/// [`rename_to`]: #structfield.rename_to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata<'a, T: ?Sized> {
+    /// Old file mode from an `old mode 100644` line
+    pub old_mode: Option<&'a T>,
+    /// New file mode from a `new mode 100755` line
+    pub new_mode: Option<&'a T>,
+    /// Mode of a newly created file from a `new file mode 100644` line
+    pub new_file_mode: Option<&'a T>,
+    /// Mode of a removed file from a `deleted file mode 100644` line
+    pub deleted_file_mode: Option<&'a T>,
+    /// The `NN%` figure from a `similarity index NN%` line
+    pub similarity_index: Option<&'a T>,
+    /// Source path from a `rename from <path>` line
+    pub rename_from: Option<&'a T>,
+    /// Destination path from a `rename to <path>` line
+    pub rename_to: Option<&'a T>,
+    /// Source path from a `copy from <path>` line
+    pub copy_from: Option<&'a T>,
+    /// Destination path from a `copy to <path>` line
+    pub copy_to: Option<&'a T>,
+    /// The `<hash>..<hash> <mode>` text from an `index ` line
+    pub index: Option<&'a T>,
+}
+
+impl<T: ?Sized> Default for FileMetadata<'_, T> {
+    fn default() -> Self {
+        Self {
+            old_mode: None,
+            new_mode: None,
+            new_file_mode: None,
+            deleted_file_mode: None,
+            similarity_index: None,
+            rename_from: None,
+            rename_to: None,
+            copy_from: None,
+            copy_to: None,
+            index: None,
+        }
+    }
+}
+
+impl<T: ?Sized> FileMetadata<'_, T> {
+    /// Whether the file was renamed, as indicated by `rename from`/`rename to`
+    pub fn is_rename(&self) -> bool {
+        self.rename_from.is_some() || self.rename_to.is_some()
+    }
+
+    /// Whether the file was copied, as indicated by `copy from`/`copy to`
+    pub fn is_copy(&self) -> bool {
+        self.copy_from.is_some() || self.copy_to.is_some()
+    }
+
+    /// Whether the file was newly created in this diff
+    pub fn is_new_file(&self) -> bool {
+        self.new_file_mode.is_some()
+    }
+
+    /// Whether the file was deleted in this diff
+    pub fn is_deleted_file(&self) -> bool {
+        self.deleted_file_mode.is_some()
+    }
+
+    /// Whether the file's mode changed, as indicated by `old mode`/`new mode`
+    pub fn is_mode_change(&self) -> bool {
+        self.old_mode.is_some() || self.new_mode.is_some()
+    }
 }
 
-// Skip to the first "--- " line, skipping any preamble lines like "diff --git", etc.
-fn skip_header_preamble<'a, T: Text + ?Sized>(parser: &mut Parser<'a, T>) -> Result<()> {
+// Consume the extended header lines that precede the "--- "/"+++ " pair,
+// collecting any git metadata (renames, mode changes, index line, etc.) along
+// the way. Lines that aren't recognized git headers (such as the "diff --git"
+// boundary marker itself) are skipped.
+fn parse_git_preamble<'a, T: Text + ?Sized>(
+    parser: &mut Parser<'a, T>,
+) -> Result<FileMetadata<'a, T>> {
+    let mut metadata = FileMetadata::default();
+    // Whether we've already consumed this section's `diff --git`/`Index: `
+    // marker; a second one means the next file's section has begun.
+    let mut seen_marker = false;
+
     while let Some(line) = parser.peek() {
         if line.starts_with("--- ") {
             break;
         }
+
+        if is_file_marker(line) {
+            if seen_marker {
+                break;
+            }
+            seen_marker = true;
+            parser.next()?;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("old mode ") {
+            metadata.old_mode = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("new mode ") {
+            metadata.new_mode = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("new file mode ") {
+            metadata.new_file_mode = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("deleted file mode ") {
+            metadata.deleted_file_mode = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("similarity index ") {
+            metadata.similarity_index = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("rename from ") {
+            metadata.rename_from = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("rename to ") {
+            metadata.rename_to = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("copy from ") {
+            metadata.copy_from = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("copy to ") {
+            metadata.copy_to = Some(trim_newline(rest));
+        } else if let Some(rest) = line.strip_prefix("index ") {
+            metadata.index = Some(trim_newline(rest));
+        }
+
         parser.next()?;
     }
 
-    Ok(())
+    Ok(metadata)
+}
+
+// Strip a trailing newline off an extended header value without failing when
+// the line was the last in the input and therefore unterminated. Handles CRLF
+// so metadata values don't keep a dangling `\r` on Windows patches.
+fn trim_newline<T: Text + ?Sized>(s: &T) -> &T {
+    if let Some(stripped) = s.strip_suffix("\r\n") {
+        stripped
+    } else {
+        s.strip_suffix("\n").unwrap_or(s)
+    }
+}
+
+// A line that introduces a file section's preamble (as opposed to the
+// "--- " line that introduces its body).
+fn is_file_marker<T: Text + ?Sized>(line: &T) -> bool {
+    line.starts_with("diff --git") || line.starts_with("Index: ")
+}
+
+// A line that begins a new file section and therefore ends the current one,
+// used to stop hunk parsing at a file boundary instead of running to EOF.
+fn is_file_boundary<T: Text + ?Sized>(line: &T) -> bool {
+    line.starts_with("--- ") || is_file_marker(line)
 }
 
 fn parse_filename<'a, T: Text + ToOwned + ?Sized>(
     prefix: &str,
+    line_number: usize,
     line: &'a T,
 ) -> Result<Cow<'a, [u8]>> {
-    let line = line
-        .strip_prefix(prefix)
-        .ok_or_else(|| ParsePatchError::new("unable to parse filename"))?;
+    let stripped = line.strip_prefix(prefix).ok_or_else(|| {
+        ParsePatchError::new(ParsePatchErrorKind::BadFilename, line_number, line)
+    })?;
 
-    let filename = if let Some((filename, _)) = line.split_at_exclusive("\t") {
+    let filename = if let Some((filename, _)) = stripped.split_at_exclusive("\t") {
         filename
-    } else if let Some((filename, _)) = line.split_at_exclusive("\n") {
+    } else if let Some((filename, _)) = stripped.split_at_exclusive("\r\n") {
+        filename
+    } else if let Some((filename, _)) = stripped.split_at_exclusive("\n") {
         filename
     } else {
-        return Err(ParsePatchError::new("filename unterminated"));
+        return Err(ParsePatchError::new(
+            ParsePatchErrorKind::BadFilename,
+            line_number,
+            line,
+        ));
     };
 
     let filename = if let Some(quoted) = is_quoted(filename) {
-        escaped_filename(quoted)?
+        escaped_filename(line_number, quoted)
     } else {
-        unescaped_filename(filename)?
-    };
+        unescaped_filename(line_number, filename)
+    }
+    // Report the whole header line rather than just the filename fragment.
+    .map_err(|_| ParsePatchError::new(ParsePatchErrorKind::BadFilename, line_number, line))?;
 
     Ok(filename)
 }
@@ -133,31 +565,50 @@ fn is_quoted<T: Text + ?Sized>(s: &T) -> Option<&T> {
     s.strip_prefix("\"").and_then(|s| s.strip_suffix("\""))
 }
 
-fn unescaped_filename<'a, T: Text + ToOwned + ?Sized>(filename: &'a T) -> Result<Cow<'a, [u8]>> {
+fn unescaped_filename<'a, T: Text + ToOwned + ?Sized>(
+    line_number: usize,
+    filename: &'a T,
+) -> Result<Cow<'a, [u8]>> {
     let bytes = filename.as_bytes();
 
     if bytes.iter().any(|b| ESCAPED_CHARS_BYTES.contains(b)) {
-        return Err(ParsePatchError::new("invalid char in unquoted filename"));
+        return Err(ParsePatchError::new(
+            ParsePatchErrorKind::BadFilename,
+            line_number,
+            filename,
+        ));
     }
 
     Ok(bytes.into())
 }
 
-fn escaped_filename<T: Text + ToOwned + ?Sized>(escaped: &T) -> Result<Cow<'_, [u8]>> {
+fn escaped_filename<T: Text + ToOwned + ?Sized>(
+    line_number: usize,
+    escaped: &T,
+) -> Result<Cow<'_, [u8]>> {
     let mut filename = Vec::new();
 
     let mut chars = escaped.as_bytes().iter().copied();
     while let Some(c) = chars.next() {
         if c == b'\\' {
-            match chars
-                .next()
-                .ok_or_else(|| ParsePatchError::new("expected escaped character"))?
-            {
+            match chars.next().ok_or_else(|| {
+                ParsePatchError::new(ParsePatchErrorKind::BadFilename, line_number, escaped)
+            })? {
                 b'n' | b't' | b'0' | b'r' | b'\"' | b'\\' => filename.push(c),
-                _ => return Err(ParsePatchError::new("invalid escaped character")),
+                _ => {
+                    return Err(ParsePatchError::new(
+                        ParsePatchErrorKind::BadFilename,
+                        line_number,
+                        escaped,
+                    ))
+                }
             }
         } else if ESCAPED_CHARS_BYTES.contains(&c) {
-            return Err(ParsePatchError::new("invalid unescaped character"));
+            return Err(ParsePatchError::new(
+                ParsePatchErrorKind::BadFilename,
+                line_number,
+                escaped,
+            ));
         } else {
             filename.push(c);
         }
@@ -166,135 +617,241 @@ fn escaped_filename<T: Text + ToOwned + ?Sized>(escaped: &T) -> Result<Cow<'_, [
     Ok(filename.into())
 }
 
-fn verify_hunks_in_order<T: ?Sized>(hunks: &[Hunk<'_, T>]) -> bool {
-    for hunk in hunks.windows(2) {
+// Verify that the hunks are in sorted order and don't overlap. On failure
+// returns the index of the second hunk of the first offending pair so the
+// caller can report its line number.
+fn verify_hunks_in_order<T: ?Sized>(hunks: &[Hunk<'_, T>]) -> Option<usize> {
+    for (i, hunk) in hunks.windows(2).enumerate() {
         if hunk[0].old_range.end() >= hunk[1].old_range.start()
             || hunk[0].new_range.end() >= hunk[1].new_range.start()
         {
-            return false;
+            return Some(i + 1);
         }
     }
-    true
+    None
 }
 
 fn hunks<'a, T: Text + ?Sized>(parser: &mut Parser<'a, T>) -> Result<Vec<Hunk<'a, T>>> {
     let mut hunks = Vec::new();
-    while parser.peek().is_some() {
+    // The 1-based line number of the `@@` header for each parsed hunk.
+    let mut hunk_lines = Vec::new();
+    while let Some(line) = parser.peek() {
+        // Stop at the next file section rather than running all the way to EOF.
+        if is_file_boundary(line) {
+            break;
+        }
+        let header_line = parser.peek_line_number();
         hunks.push(hunk(parser)?);
+        hunk_lines.push(header_line);
     }
 
     // check and verify that the Hunks are in sorted order and don't overlap
-    if !verify_hunks_in_order(&hunks) {
-        return Err(ParsePatchError::new("Hunks not in order or overlap"));
+    if let Some(i) = verify_hunks_in_order(&hunks) {
+        return Err(ParsePatchError {
+            kind: ParsePatchErrorKind::HunksOutOfOrder,
+            line: hunk_lines[i],
+            text: String::new(),
+        });
     }
 
     Ok(hunks)
 }
 
+// Like `hunks`, but accumulates diagnostics into `errors` and resynchronizes
+// past a malformed hunk instead of propagating the first failure.
+fn hunks_lenient<'a, T: Text + ?Sized>(
+    parser: &mut Parser<'a, T>,
+    errors: &mut Vec<ParsePatchError>,
+) -> Vec<Hunk<'a, T>> {
+    let mut hunks = Vec::new();
+    let mut hunk_lines = Vec::new();
+    while let Some(line) = parser.peek() {
+        if is_file_boundary(line) {
+            break;
+        }
+        let header_line = parser.peek_line_number();
+        match hunk(parser) {
+            Ok(hunk) => {
+                hunks.push(hunk);
+                hunk_lines.push(header_line);
+            }
+            Err(e) => {
+                errors.push(e);
+                resynchronize(parser);
+            }
+        }
+    }
+
+    // Report every ordering/overlap problem rather than just the first, dropping
+    // the offending hunk each time so the scan makes progress.
+    while let Some(i) = verify_hunks_in_order(&hunks) {
+        errors.push(ParsePatchError {
+            kind: ParsePatchErrorKind::HunksOutOfOrder,
+            line: hunk_lines[i],
+            text: String::new(),
+        });
+        hunks.remove(i);
+        hunk_lines.remove(i);
+    }
+
+    hunks
+}
+
+// Advance past the remainder of a malformed hunk to the next `@@` header or
+// file boundary. `hunk` has already consumed at least one line by the time it
+// fails, so the enclosing loop always makes forward progress.
+fn resynchronize<T: Text + ?Sized>(parser: &mut Parser<'_, T>) {
+    while let Some(line) = parser.peek() {
+        if line.starts_with("@") || is_file_boundary(line) {
+            break;
+        }
+        let _ = parser.next();
+    }
+}
+
 fn hunk<'a, T: Text + ?Sized>(parser: &mut Parser<'a, T>) -> Result<Hunk<'a, T>> {
-    let (range1, range2, function_context) = hunk_header(parser.next()?)?;
-    let lines = hunk_lines(parser)?;
+    let header_line = parser.next()?;
+    let header_line_number = parser.line_number();
+    let (range1, range2, function_context) = hunk_header(header_line_number, header_line)?;
+    let lines = hunk_lines(parser, range1.len, range2.len)?;
 
     // check counts of lines to see if they match the ranges in the hunk header
     let (len1, len2) = super::hunk_lines_count(&lines);
     if len1 != range1.len || len2 != range2.len {
-        return Err(ParsePatchError::new("Hunk header does not match hunk"));
+        return Err(ParsePatchError::new(
+            ParsePatchErrorKind::HunkCountMismatch,
+            header_line_number,
+            header_line,
+        ));
     }
 
     Ok(Hunk::new(range1, range2, function_context, lines))
 }
 
-fn hunk_header<T: Text + ?Sized>(input: &T) -> Result<(HunkRange, HunkRange, Option<&T>)> {
-    let input = input
-        .strip_prefix("@@ ")
-        .ok_or_else(|| ParsePatchError::new("unable to parse hunk header"))?;
+fn hunk_header<T: Text + ?Sized>(
+    line_number: usize,
+    input: &T,
+) -> Result<(HunkRange, HunkRange, Option<&T>)> {
+    let bad = || ParsePatchError::new(ParsePatchErrorKind::BadHunkHeader, line_number, input);
 
-    let (ranges, function_context) = input
-        .split_at_exclusive(" @@")
-        .ok_or_else(|| ParsePatchError::new("hunk header unterminated"))?;
+    let rest = input.strip_prefix("@@ ").ok_or_else(bad)?;
+
+    let (ranges, function_context) = rest.split_at_exclusive(" @@").ok_or_else(bad)?;
     let function_context = function_context.strip_prefix(" ");
 
-    let (range1, range2) = ranges
-        .split_at_exclusive(" ")
-        .ok_or_else(|| ParsePatchError::new("unable to parse hunk header"))?;
-    let range1 = range(
-        range1
-            .strip_prefix("-")
-            .ok_or_else(|| ParsePatchError::new("unable to parse hunk header"))?,
-    )?;
-    let range2 = range(
-        range2
-            .strip_prefix("+")
-            .ok_or_else(|| ParsePatchError::new("unable to parse hunk header"))?,
-    )?;
+    let (range1, range2) = ranges.split_at_exclusive(" ").ok_or_else(bad)?;
+    let range1 = range(line_number, input, range1.strip_prefix("-").ok_or_else(bad)?)?;
+    let range2 = range(line_number, input, range2.strip_prefix("+").ok_or_else(bad)?)?;
     Ok((range1, range2, function_context))
 }
 
-fn range<T: Text + ?Sized>(s: &T) -> Result<HunkRange> {
+fn range<T: Text + ?Sized>(line_number: usize, header: &T, s: &T) -> Result<HunkRange> {
+    let bad = || ParsePatchError::new(ParsePatchErrorKind::BadHunkHeader, line_number, header);
+
     let (start, len) = if let Some((start, len)) = s.split_at_exclusive(",") {
         (
-            start
-                .parse()
-                .ok_or_else(|| ParsePatchError::new("can't parse range"))?,
-            len.parse()
-                .ok_or_else(|| ParsePatchError::new("can't parse range"))?,
+            start.parse().ok_or_else(bad)?,
+            len.parse().ok_or_else(bad)?,
         )
     } else {
-        (
-            s.parse()
-                .ok_or_else(|| ParsePatchError::new("can't parse range"))?,
-            1,
-        )
+        (s.parse().ok_or_else(bad)?, 1)
     };
 
     Ok(HunkRange::new(start, len))
 }
 
-fn hunk_lines<'a, T: Text + ?Sized>(parser: &mut Parser<'a, T>) -> Result<Vec<Line<'a, T>>> {
+fn hunk_lines<'a, T: Text + ?Sized>(
+    parser: &mut Parser<'a, T>,
+    expected_old: usize,
+    expected_new: usize,
+) -> Result<Vec<Line<'a, T>>> {
     let mut lines: Vec<Line<'a, T>> = Vec::new();
+    let mut old = 0;
+    let mut new = 0;
     let mut no_newline_context = false;
     let mut no_newline_delete = false;
     let mut no_newline_insert = false;
 
     while let Some(line) = parser.peek() {
-        let line = if line.starts_with("@") {
-            break;
-        } else if no_newline_context {
-            return Err(ParsePatchError::new("expected end of hunk"));
-        } else if let Some(line) = line.strip_prefix(" ") {
-            Line::Context(line)
-        } else if line.starts_with("\n") {
-            Line::Context(*line)
-        } else if let Some(line) = line.strip_prefix("-") {
-            if no_newline_delete {
-                return Err(ParsePatchError::new("expected no more deleted lines"));
-            }
-            Line::Delete(line)
-        } else if let Some(line) = line.strip_prefix("+") {
-            if no_newline_insert {
-                return Err(ParsePatchError::new("expected no more inserted lines"));
-            }
-            Line::Insert(line)
-        } else if line.starts_with(NO_NEWLINE_AT_EOF) {
+        let line_number = parser.peek_line_number();
+
+        // A "\ No newline at end of file" marker retroactively strips the
+        // terminator off the previous line; it is not itself a body line and may
+        // appear after the declared counts have been reached.
+        if line.starts_with(NO_NEWLINE_AT_EOF) {
+            // The marker's own terminator shows whether the patch text is CRLF.
+            let crlf = detect_line_ending(*line) == LineEnding::Crlf;
             let last_line = lines.pop().ok_or_else(|| {
-                ParsePatchError::new("unexpected 'No newline at end of file' line")
+                ParsePatchError::new(ParsePatchErrorKind::UnexpectedLine, line_number, *line)
             })?;
-            match last_line {
+            let stripped = match last_line {
                 Line::Context(line) => {
                     no_newline_context = true;
-                    Line::Context(strip_newline(line)?)
+                    Line::Context(strip_newline(line_number, line, crlf)?)
                 }
                 Line::Delete(line) => {
                     no_newline_delete = true;
-                    Line::Delete(strip_newline(line)?)
+                    Line::Delete(strip_newline(line_number, line, crlf)?)
                 }
                 Line::Insert(line) => {
                     no_newline_insert = true;
-                    Line::Insert(strip_newline(line)?)
+                    Line::Insert(strip_newline(line_number, line, crlf)?)
                 }
+            };
+            lines.push(stripped);
+            parser.next()?;
+            continue;
+        }
+
+        // The hunk is complete once both declared line counts are satisfied. We
+        // must check this before classifying the line so that a `--- ` or
+        // `diff --git` line belonging to the *next* file section is treated as a
+        // boundary — while a `--- ` line inside the body (a deletion of content
+        // beginning with `-- `) is still consumed as a `Delete` below.
+        if old >= expected_old && new >= expected_new {
+            break;
+        }
+
+        let line = if no_newline_context {
+            return Err(ParsePatchError::new(
+                ParsePatchErrorKind::UnexpectedLine,
+                line_number,
+                *line,
+            ));
+        } else if let Some(line) = line.strip_prefix(" ") {
+            old += 1;
+            new += 1;
+            Line::Context(line)
+        } else if line.starts_with("\n") || line.starts_with("\r\n") {
+            old += 1;
+            new += 1;
+            Line::Context(*line)
+        } else if let Some(stripped) = line.strip_prefix("-") {
+            if no_newline_delete {
+                return Err(ParsePatchError::new(
+                    ParsePatchErrorKind::UnexpectedLine,
+                    line_number,
+                    *line,
+                ));
+            }
+            old += 1;
+            Line::Delete(stripped)
+        } else if let Some(stripped) = line.strip_prefix("+") {
+            if no_newline_insert {
+                return Err(ParsePatchError::new(
+                    ParsePatchErrorKind::UnexpectedLine,
+                    line_number,
+                    *line,
+                ));
             }
+            new += 1;
+            Line::Insert(stripped)
         } else {
-            return Err(ParsePatchError::new("unexpected line in hunk body"));
+            return Err(ParsePatchError::new(
+                ParsePatchErrorKind::UnexpectedLine,
+                line_number,
+                *line,
+            ));
         };
 
         lines.push(line);
@@ -304,10 +861,144 @@ fn hunk_lines<'a, T: Text + ?Sized>(parser: &mut Parser<'a, T>) -> Result<Vec<Li
     Ok(lines)
 }
 
-fn strip_newline<T: Text + ?Sized>(s: &T) -> Result<&T> {
-    if let Some(stripped) = s.strip_suffix("\n") {
+fn strip_newline<T: Text + ?Sized>(line_number: usize, s: &T, crlf: bool) -> Result<&T> {
+    // In a CRLF patch a trailing `\r\n` is the terminator and is removed; in an
+    // LF patch only the `\n` is, so a `\r` that is genuine content on the
+    // file's unterminated final line is left in place.
+    if let Some(stripped) = s.strip_suffix("\r\n").filter(|_| crlf) {
+        Ok(stripped)
+    } else if let Some(stripped) = s.strip_suffix("\n") {
         Ok(stripped)
     } else {
-        Err(ParsePatchError::new("missing newline"))
+        Err(ParsePatchError::new(
+            ParsePatchErrorKind::UnexpectedLine,
+            line_number,
+            s,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_lenient};
+    use crate::patch::{Line, LineEnding, ParsePatchErrorKind, PatchSet};
+
+    #[test]
+    fn error_reports_kind_line_and_offending_text() {
+        // The hunk header on line 3 is missing its ranges.
+        let input = "--- a\n+++ b\n@@ bad @@\n";
+        let err = parse(input).unwrap_err();
+
+        assert_eq!(err.kind(), ParsePatchErrorKind::BadHunkHeader);
+        assert_eq!(err.line(), 3);
+        assert_eq!(err.text(), "@@ bad @@\n");
+        assert_eq!(
+            err.to_string(),
+            "error parsing patch at line 3: malformed hunk header"
+        );
+    }
+
+    #[test]
+    fn crlf_round_trips_without_corruption() {
+        let input = "--- a\r\n+++ b\r\n@@ -1 +1 @@\r\n-foo\r\n+bar\r\n";
+        let patch = parse(input).unwrap();
+
+        assert_eq!(patch.line_ending(), LineEnding::Crlf);
+        assert_eq!(patch.hunks().len(), 1);
+        // The trailing CR is preserved in the line content.
+        assert_eq!(patch.hunks()[0].lines()[0], Line::Delete("foo\r\n"));
+        assert_eq!(patch.hunks()[0].lines()[1], Line::Insert("bar\r\n"));
+    }
+
+    #[test]
+    fn patch_set_counts_each_file_section() {
+        let input = "\
+--- a\n+++ b\n@@ -1 +1 @@\n-x\n+y\n\
+--- c\n+++ d\n@@ -1 +1 @@\n-m\n+n\n";
+        let set = PatchSet::from_str(input).unwrap();
+
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.patches()[0].original(), "a");
+        assert_eq!(set.patches()[1].modified(), "d");
+    }
+
+    #[test]
+    fn deletion_of_a_dashed_line_is_not_treated_as_a_boundary() {
+        // The deleted line's content begins with "-- ", which git emits as a
+        // raw "--- " line inside the hunk body.
+        let input = "--- a\n+++ b\n@@ -1 +0,0 @@\n--- foo\n";
+        let patch = parse(input).unwrap();
+
+        assert_eq!(patch.hunks().len(), 1);
+        assert_eq!(patch.hunks()[0].lines()[0], Line::Delete("-- foo\n"));
+    }
+
+    #[test]
+    fn rename_only_header_has_no_hunk_body() {
+        let input = "\
+diff --git a/old b/new\n\
+similarity index 100%\n\
+rename from old\n\
+rename to new\n";
+        let set = PatchSet::from_str(input).unwrap();
+
+        assert_eq!(set.len(), 1);
+        let patch = &set.patches()[0];
+        assert!(patch.metadata().is_rename());
+        assert!(patch.hunks().is_empty());
+        assert_eq!(patch.metadata().rename_from, Some("old"));
+        assert_eq!(patch.metadata().rename_to, Some("new"));
+        assert_eq!(patch.metadata().similarity_index, Some("100%"));
+    }
+
+    #[test]
+    fn new_file_header_is_parsed() {
+        let input = "\
+diff --git a/new.txt b/new.txt\n\
+new file mode 100644\n\
+index 0000000..e69de29\n\
+--- /dev/null\n\
++++ b/new.txt\n\
+@@ -0,0 +1 @@\n\
++hello\n";
+        let patch = parse(input).unwrap();
+
+        assert!(patch.metadata().is_new_file());
+        assert!(!patch.metadata().is_deleted_file());
+        assert_eq!(patch.metadata().new_file_mode, Some("100644"));
+        assert_eq!(patch.metadata().index, Some("0000000..e69de29"));
+        assert_eq!(patch.hunks().len(), 1);
+    }
+
+    #[test]
+    fn mode_change_header_is_parsed() {
+        let input = "\
+diff --git a/f b/f\n\
+old mode 100644\n\
+new mode 100755\n\
+index abc1234..def5678 100755\n\
+--- a/f\n\
++++ b/f\n\
+@@ -1 +1 @@\n\
+-x\n\
++y\n";
+        let patch = parse(input).unwrap();
+
+        assert!(patch.metadata().is_mode_change());
+        assert!(!patch.metadata().is_new_file());
+        assert_eq!(patch.metadata().old_mode, Some("100644"));
+        assert_eq!(patch.metadata().new_mode, Some("100755"));
+        assert_eq!(patch.metadata().index, Some("abc1234..def5678 100755"));
+    }
+
+    #[test]
+    fn lenient_collects_every_diagnostic_and_makes_progress() {
+        let input = "--- a\n+++ b\n@@ bad header @@\n something\n@@ also bad @@\n more\n";
+        let (patch, errors) = parse_lenient(input);
+
+        // The file header parsed, so we still get a (hunk-less) patch back.
+        assert!(patch.is_some());
+        // Both malformed hunks are reported rather than just the first.
+        assert_eq!(errors.len(), 2);
     }
 }